@@ -1,18 +1,26 @@
 // examples/cli.rs
-use audiocap_lib::{build_ffmpeg_args, check_ffmpeg, next_filename, start_background, start_foreground, list_devices, stop_pid, is_pid_alive, RecorderConfig, Container};
+use audiocap_lib::{build_ffmpeg_args, check_ffmpeg, next_filename, start_background, start_foreground, list_devices_structured, stop_pid, is_pid_alive, OutputSink, RecorderConfig, Container};
 use std::path::PathBuf;
 use anyhow::Result;
 use std::fs;
 
 fn main() -> Result<()> {
+    // List devices first so you can pick a mic/system override.
+    for dev in list_devices_structured()? {
+        println!(
+            "{} [{}] input={} output={} default={} channels={}",
+            dev.name, dev.host, dev.is_input, dev.is_output, dev.is_default, dev.channels
+        );
+    }
+
     // Example: start background recording
     check_ffmpeg()?;
     let mut cfg = RecorderConfig::default();
-    cfg.format = Container::Wav;
+    cfg.codec = audiocap_lib::CodecConfig::for_container(Container::Wav);
     fs::create_dir_all(&cfg.out_dir)?;
 
-    let outfile = next_filename(&cfg.out_dir, cfg.format);
-    let args = build_ffmpeg_args(&cfg, &outfile, None, None, None)?;
+    let outfile = next_filename(&cfg.out_dir, cfg.codec.container);
+    let args = build_ffmpeg_args(&cfg, &OutputSink::File(outfile.clone()), None, None, None)?;
     let pid = start_background(&args)?;
     println!("started background pid={} file={}", pid, outfile.display());
 
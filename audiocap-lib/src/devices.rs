@@ -0,0 +1,168 @@
+//! Structured audio device enumeration, backed by `cpal`.
+//!
+//! Replaces the old `list_devices` behavior of shelling out to
+//! ffmpeg/pactl/pw-cli and dumping human-readable text to stderr. Callers
+//! that need a real data structure (e.g. the Tauri frontend populating a
+//! dropdown) should use `list_devices_structured` instead.
+
+use crate::LinuxBackend;
+use anyhow::Result;
+use cpal::traits::{DeviceTrait, HostTrait};
+
+/// One enumerated audio device, independent of platform.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct DeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub is_default: bool,
+    pub is_input: bool,
+    pub is_output: bool,
+    pub supported_sample_rates: Vec<u32>,
+    pub channels: u16,
+}
+
+/// Enumerate every input and output device across every available host
+/// (WASAPI/DirectShow on Windows, CoreAudio on macOS, ALSA/PulseAudio/JACK
+/// on Linux).
+pub fn list_devices_structured() -> Result<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+
+    for host_id in cpal::available_hosts() {
+        let host = cpal::host_from_id(host_id)?;
+        let host_name = host_id.name().to_string();
+
+        let default_input = host.default_input_device().and_then(|d| d.name().ok());
+        let default_output = host.default_output_device().and_then(|d| d.name().ok());
+
+        for device in host.devices()? {
+            let name = match device.name() {
+                Ok(n) => n,
+                Err(_) => continue,
+            };
+
+            let is_input = device.default_input_config().is_ok();
+            let is_output = device.default_output_config().is_ok();
+            if !is_input && !is_output {
+                continue;
+            }
+
+            let supported_sample_rates = supported_sample_rates(&device, is_input);
+            let channels = device_channels(&device, is_input);
+
+            let is_default = (is_input && default_input.as_deref() == Some(name.as_str()))
+                || (is_output && default_output.as_deref() == Some(name.as_str()));
+
+            devices.push(DeviceInfo {
+                id: format!("{}::{}", host_name, name),
+                name,
+                host: host_name.clone(),
+                is_default,
+                is_input,
+                is_output,
+                supported_sample_rates,
+                channels,
+            });
+        }
+    }
+
+    Ok(devices)
+}
+
+fn supported_sample_rates(device: &cpal::Device, is_input: bool) -> Vec<u32> {
+    let configs = if is_input {
+        device.supported_input_configs()
+    } else {
+        device.supported_output_configs()
+    };
+
+    match configs {
+        Ok(configs) => {
+            let mut rates: Vec<u32> = configs
+                .flat_map(|c| [c.min_sample_rate().0, c.max_sample_rate().0])
+                .collect();
+            rates.sort_unstable();
+            rates.dedup();
+            rates
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+fn device_channels(device: &cpal::Device, is_input: bool) -> u16 {
+    let config = if is_input {
+        device.default_input_config()
+    } else {
+        device.default_output_config()
+    };
+    config.map(|c| c.channels()).unwrap_or(0)
+}
+
+/// Map a `DeviceInfo` (as returned by `list_devices_structured`) back into
+/// the platform-specific ffmpeg input string that `platform_inputs_with_overrides`
+/// expects as a mic/system override. The mapping is derived from `info.host`,
+/// not the bare device name, since e.g. a cpal ALSA device name is not a
+/// valid PulseAudio source. Callers should also apply `recommended_backend`
+/// to `RecorderConfig` so the `-f` prelude ffmpeg uses matches this input.
+pub fn ffmpeg_input_for_device(info: &DeviceInfo) -> Result<String> {
+    #[cfg(target_os = "windows")]
+    {
+        if host_is(info, "wasapi") {
+            Ok(info.name.clone())
+        } else {
+            Ok(format!("audio={}", info.name))
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(format!(":{}", macos_avfoundation_index(info)?))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // JACK device names (e.g. "system:capture_1") are already valid
+        // ffmpeg `-f jack` inputs. Anything else comes from cpal's ALSA
+        // host, so pass the ALSA device name through as-is and pair it
+        // with `-f alsa` via `recommended_backend`/`LinuxBackend::Alsa`
+        // rather than treating it as a PulseAudio source.
+        Ok(info.name.clone())
+    }
+}
+
+/// The `RecorderConfig` backend selection (`wasapi` / `linux_backend`) that
+/// matches `info`'s host, so callers wiring a structured device into
+/// `RecorderConfig` don't have to duplicate the host checks
+/// `ffmpeg_input_for_device` uses internally.
+pub fn recommended_backend(info: &DeviceInfo) -> (bool, LinuxBackend) {
+    let wasapi = host_is(info, "wasapi");
+    let linux_backend = if host_is(info, "jack") {
+        LinuxBackend::Jack
+    } else {
+        LinuxBackend::Alsa
+    };
+    (wasapi, linux_backend)
+}
+
+fn host_is(info: &DeviceInfo, needle: &str) -> bool {
+    info.host.to_lowercase().contains(needle)
+}
+
+/// ffmpeg's avfoundation input addresses audio devices by index, not name
+/// (`-i ":0"`). cpal doesn't expose that index directly, so this re-walks
+/// the same host's input devices and uses the matching position; this is
+/// best-effort and assumes avfoundation and cpal enumerate devices in the
+/// same order, which holds in practice since both ultimately list
+/// CoreAudio's default input device set.
+#[cfg(target_os = "macos")]
+fn macos_avfoundation_index(info: &DeviceInfo) -> Result<usize> {
+    let host_id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name() == info.host)
+        .ok_or_else(|| anyhow::anyhow!("host '{}' not found", info.host))?;
+    let host = cpal::host_from_id(host_id)?;
+
+    host.input_devices()?
+        .position(|d| d.name().map(|n| n == info.name).unwrap_or(false))
+        .ok_or_else(|| anyhow::anyhow!("device '{}' not found on host '{}'", info.name, info.host))
+}
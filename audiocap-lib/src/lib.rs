@@ -9,31 +9,133 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use which::which;
 
+mod devices;
+pub mod monitor;
+pub mod spectrum;
+pub mod transcribe;
+pub mod vad;
+pub use devices::{ffmpeg_input_for_device, list_devices_structured, recommended_backend, DeviceInfo};
+pub use monitor::InputMonitor;
+pub use spectrum::{SpectrumAnalyzer, SpectrumFrame};
+pub use transcribe::{transcribe, ModelSize, TranscribeOptions, Transcript};
+pub use vad::run_voice_activated;
+
+/// Which Linux audio backend ffmpeg should use. Mirrors `RecorderConfig::wasapi`
+/// on Windows: a device picked from `list_devices_structured` may be
+/// ALSA- or JACK-backed rather than PulseAudio, so the backend has to match
+/// the device or ffmpeg's input id won't resolve.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LinuxBackend {
+    Pulse,
+    Alsa,
+    Jack,
+}
+
 /// Container/format for output files
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Container {
     Wav,
     Mp3,
+    Flac,
+    Opus,
+    Aac,
+}
+
+/// Codec/bitrate/sample-rate/channel settings for the mixed output, so
+/// callers can trade size vs. quality instead of getting a fixed codec at a
+/// fixed bitrate per container.
+#[derive(Clone, Debug)]
+pub struct CodecConfig {
+    pub container: Container,
+    /// ffmpeg `-c:a` codec name, e.g. "libmp3lame", "flac", "libopus", "aac".
+    pub codec: &'static str,
+    /// Target bitrate in kbps. `None` for codecs without a meaningful
+    /// bitrate knob (e.g. WAV, FLAC).
+    pub bitrate: Option<u32>,
+    /// Output sample rate in Hz. `None` leaves it to ffmpeg/the input.
+    pub sample_rate: Option<u32>,
+    /// Output channel count. `None` leaves it to ffmpeg/the input (the mix
+    /// filter already produces a single stream from mic + system).
+    pub channels: Option<u16>,
+}
+
+impl CodecConfig {
+    /// Sensible codec/bitrate defaults for each container.
+    pub fn for_container(container: Container) -> Self {
+        let (codec, bitrate) = match container {
+            Container::Wav => ("pcm_s16le", None),
+            Container::Mp3 => ("libmp3lame", Some(192)),
+            Container::Flac => ("flac", None),
+            Container::Opus => ("libopus", Some(128)),
+            Container::Aac => ("aac", Some(192)),
+        };
+        Self {
+            container,
+            codec,
+            bitrate,
+            sample_rate: None,
+            channels: None,
+        }
+    }
+}
+
+impl Default for CodecConfig {
+    fn default() -> Self {
+        Self::for_container(Container::Wav)
+    }
+}
+
+/// Where the mixed output audio should go.
+#[derive(Clone, Debug)]
+pub enum OutputSink {
+    /// Write to a file on disk (the existing, default behavior).
+    File(PathBuf),
+    /// Push to a live endpoint, e.g. `rtmp://` or `icecast://`. The ffmpeg
+    /// output format is chosen from the URL scheme instead of a file
+    /// extension.
+    Url(String),
+    /// Write the encoded stream to ffmpeg's stdout (`pipe:1`) so a Rust
+    /// caller can read it directly, e.g. to forward over its own transport.
+    Stdout,
 }
 
 /// Minimal config controlling platform behavior.
 #[derive(Clone, Debug)]
 pub struct RecorderConfig {
     pub out_dir: PathBuf,
-    pub format: Container,
+    pub codec: CodecConfig,
     pub prefer_pipewire: bool,
     pub wasapi: bool,
+    /// Which Linux backend ffmpeg should target. Only consulted on Linux;
+    /// defaults to `Pulse` to preserve the original `@DEFAULT_SOURCE@`/
+    /// `@DEFAULT_SINK@.monitor` behavior.
+    pub linux_backend: LinuxBackend,
+    /// RMS level (roughly 0.0-1.0) above which voice-activated mode starts
+    /// recording. `None` disables voice-activated mode entirely.
+    pub vad_threshold: Option<f32>,
+    /// How long the level must stay below the (hysteresis-adjusted) close
+    /// threshold before voice-activated mode stops the current segment.
+    pub silence_timeout: Duration,
+    /// Split the recording into fixed-duration files instead of one
+    /// unbroken capture, so multi-hour recordings stay recoverable if the
+    /// process is killed. `None` disables segmentation.
+    pub segment_seconds: Option<u64>,
 }
 
 impl Default for RecorderConfig {
     fn default() -> Self {
         Self {
             out_dir: PathBuf::from("recordings"),
-            format: Container::Wav,
+            codec: CodecConfig::default(),
             prefer_pipewire: false,
             wasapi: false,
+            linux_backend: LinuxBackend::Pulse,
+            vad_threshold: None,
+            silence_timeout: Duration::from_secs(2),
+            segment_seconds: None,
         }
     }
 }
@@ -50,30 +152,39 @@ pub fn next_filename(out_dir: &Path, format: Container) -> PathBuf {
     let ext = match format {
         Container::Wav => "wav",
         Container::Mp3 => "mp3",
+        Container::Flac => "flac",
+        Container::Opus => "opus",
+        Container::Aac => "aac",
     };
     out_dir.join(format!("recording_{}.{}", ts, ext))
 }
 
 /// Build ffmpeg command-line args given a RecorderConfig and optional overrides.
+/// `sink`: where the mixed audio should go (file, live URL, or stdout)
 /// `duration`: Option like "00:10:00" or "600"
 /// `mic_override`, `system_override`: optional device strings (platform dependent)
 pub fn build_ffmpeg_args(
     cfg: &RecorderConfig,
-    outfile: &Path,
+    sink: &OutputSink,
     duration: Option<&str>,
     mic_override: Option<&str>,
     system_override: Option<&str>,
 ) -> Result<Vec<String>> {
-    // Ensure out_dir exists
-    fs::create_dir_all(&cfg.out_dir).context("failed to create output directory")?;
+    if matches!(sink, OutputSink::File(_)) {
+        fs::create_dir_all(&cfg.out_dir).context("failed to create output directory")?;
+    }
 
-    platform_build_args(cfg, outfile, duration, mic_override, system_override)
+    platform_build_args(cfg, sink, duration, mic_override, system_override)
 }
 
 /// Start a foreground recording (blocks until ffmpeg exits). Handles Ctrl-C to stop ffmpeg.
 ///
 /// Returns () if successful (ffmpeg exit status was success).
 pub fn start_foreground(args: &[String]) -> Result<()> {
+    if targets_stdout(args) {
+        bail!("args target stdout (pipe:1); use start_piped instead of start_foreground so the byte stream isn't silently dropped");
+    }
+
     let child = Command::new("ffmpeg")
         .args(args)
         .stdin(Stdio::null())
@@ -104,10 +215,39 @@ pub fn start_foreground(args: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// Spawn ffmpeg with its stdout piped, for `OutputSink::Stdout`. The caller
+/// owns the returned `Child` and is responsible for reading its stdout
+/// (e.g. to forward the byte stream over a QUIC/MoQ publisher) and waiting
+/// on it; this does not block or install a Ctrl-C handler like
+/// `start_foreground`.
+pub fn start_piped(args: &[String]) -> Result<std::process::Child> {
+    Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("failed to spawn streaming ffmpeg")
+}
+
+/// True if `args` (as built by `build_ffmpeg_args` for `OutputSink::Stdout`)
+/// write to ffmpeg's stdout, i.e. end in `-f <fmt> pipe:1`. `start_foreground`
+/// and `start_background` refuse such args, since they don't expose ffmpeg's
+/// stdout to the caller and would otherwise silently drop the encoded
+/// stream (or, for `start_background`, eventually block ffmpeg once the
+/// unread pipe fills).
+fn targets_stdout(args: &[String]) -> bool {
+    args.iter().any(|a| a == "pipe:1")
+}
+
 /// Start ffmpeg detached (background). Returns the child's PID on success.
 ///
 /// Note: this uses `setsid()` on Unix and DETACHED_PROCESS flags on Windows.
 pub fn start_background(args: &[String]) -> Result<u32> {
+    if targets_stdout(args) {
+        bail!("args target stdout (pipe:1); use start_piped instead of start_background so the byte stream isn't silently dropped");
+    }
+
     let mut cmd = Command::new("ffmpeg");
     cmd.args(args)
         .stdin(Stdio::null())
@@ -178,47 +318,6 @@ pub fn is_pid_alive(pid: u32) -> Result<bool> {
     }
 }
 
-/// List audio devices for the current platform (prints to stdout/stderr).
-/// `_prefer_pipewire` and `_wasapi` are kept for callers who may want to switch behavior.
-pub fn list_devices(_prefer_pipewire: bool, _wasapi: bool) -> Result<()> {
-    #[cfg(target_os = "windows")]
-    {
-        if _wasapi {
-            eprintln!("=== Windows (WASAPI) devices ===");
-            let _ = Command::new("ffmpeg")
-                .args(["-hide_banner", "-f", "wasapi", "-list_devices", "true", "-i", "dummy"])
-                .status();
-        } else {
-            eprintln!("=== Windows (DirectShow) devices ===");
-            let _ = Command::new("ffmpeg")
-                .args(["-hide_banner", "-list_devices", "true", "-f", "dshow", "-i", "dummy"])
-                .status();
-        }
-        eprintln!("\nTips: For system audio you may need Stereo Mix or a virtual loopback device.");
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        eprintln!("=== macOS (AVFoundation) devices ===");
-        let _ = Command::new("ffmpeg")
-            .args(["-hide_banner", "-f", "avfoundation", "-list_devices", "true", "-i", ""])
-            .status();
-        eprintln!("Note: macOS requires a loopback device (BlackHole/Loopback) for system audio.");
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        eprintln!("=== Linux: PulseAudio (pactl) ===");
-        let _ = Command::new("pactl").args(["list", "short", "sources"]).status();
-        let _ = Command::new("pactl").args(["info"]).status();
-
-        eprintln!("\n=== Linux: PipeWire (pw-cli) ===");
-        let _ = Command::new("pw-cli").args(["ls", "Node"]).status();
-    }
-
-    Ok(())
-}
-
 /* ----------------- Internal helpers below ----------------- */
 
 #[derive(Clone, Default)]
@@ -236,7 +335,7 @@ struct FormatInputs {
 // Builds args using platform-specific choices and optional overrides.
 fn platform_build_args(
     cfg: &RecorderConfig,
-    outfile: &Path,
+    sink: &OutputSink,
     duration_override: Option<&str>,
     mic_override: Option<&str>,
     system_override: Option<&str>,
@@ -272,18 +371,85 @@ fn platform_build_args(
         "amix=inputs=2:duration=longest:dropout_transition=2".into(),
     ]);
 
-    // Output codec/container
-    match cfg.format {
-        Container::Wav => {
-            args.extend(["-c:a".into(), "pcm_s16le".into()]);
+    // Output codec
+    args.extend(["-c:a".into(), cfg.codec.codec.to_string()]);
+    if let Some(kbps) = cfg.codec.bitrate {
+        args.extend(["-b:a".into(), format!("{}k", kbps)]);
+    }
+    if let Some(sample_rate) = cfg.codec.sample_rate {
+        args.extend(["-ar".into(), sample_rate.to_string()]);
+    }
+    if let Some(channels) = cfg.codec.channels {
+        args.extend(["-ac".into(), channels.to_string()]);
+    }
+
+    args.extend(sink_args(cfg, sink));
+    Ok(args)
+}
+
+/// ffmpeg's `-f` muxer name for a container, used whenever we can't rely on
+/// ffmpeg inferring the format from a file extension (URLs, pipes, segments).
+fn container_format_name(format: Container) -> &'static str {
+    match format {
+        Container::Wav => "wav",
+        Container::Mp3 => "mp3",
+        Container::Flac => "flac",
+        Container::Opus => "opus",
+        Container::Aac => "adts",
+    }
+}
+
+/// Output-side args for `sink`: the muxer/format flags ffmpeg needs plus the
+/// final destination argument. When `cfg.segment_seconds` is set and the
+/// sink is a file, this overrides the sink entirely with ffmpeg's segment
+/// muxer so long captures are split into fixed-duration, independently
+/// playable files.
+fn sink_args(cfg: &RecorderConfig, sink: &OutputSink) -> Vec<String> {
+    if let (OutputSink::File(path), Some(segment_seconds)) = (sink, cfg.segment_seconds) {
+        return vec![
+            "-f".into(),
+            "segment".into(),
+            "-segment_time".into(),
+            segment_seconds.to_string(),
+            "-reset_timestamps".into(),
+            "1".into(),
+            segment_template(path),
+        ];
+    }
+
+    match sink {
+        OutputSink::File(path) => vec![path.to_string_lossy().to_string()],
+        OutputSink::Url(url) if url.starts_with("rtmp://") => {
+            vec!["-f".into(), "flv".into(), url.clone()]
         }
-        Container::Mp3 => {
-            args.extend(["-c:a".into(), "libmp3lame".into(), "-b:a".into(), "192k".into()]);
+        OutputSink::Url(url) if url.starts_with("icecast://") => vec![
+            "-content_type".into(),
+            "audio/mpeg".into(),
+            "-f".into(),
+            "mp3".into(),
+            url.clone(),
+        ],
+        OutputSink::Url(url) => {
+            vec!["-f".into(), container_format_name(cfg.codec.container).into(), url.clone()]
         }
+        OutputSink::Stdout => vec![
+            "-f".into(),
+            container_format_name(cfg.codec.container).into(),
+            "pipe:1".into(),
+        ],
     }
+}
 
-    args.push(outfile.to_string_lossy().to_string());
-    Ok(args)
+/// Turn a `next_filename`-style path into a numbered segment template, e.g.
+/// `recording_2026-07-28_07-23-36.wav` -> `recording_2026-07-28_07-23-36_%03d.wav`.
+fn segment_template(path: &Path) -> String {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("wav");
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    parent
+        .join(format!("{}_%03d.{}", stem, ext))
+        .to_string_lossy()
+        .to_string()
 }
 
 fn platform_inputs_with_overrides(
@@ -344,6 +510,28 @@ fn platform_inputs_with_overrides(
         let mut pre = DevPrelude::default();
         let mut fmt = FormatInputs::default();
 
+        match cfg.linux_backend {
+            LinuxBackend::Jack => {
+                pre.system_prelude = vec!["-f".into(), "jack".into()];
+                pre.mic_prelude = vec!["-f".into(), "jack".into()];
+
+                let mic = mic_override.map(|s| s.to_string()).unwrap_or_else(|| "system:capture_1".into());
+                let sys = system_override.map(|s| s.to_string()).unwrap_or_else(|| "system:playback_1".into());
+
+                return Ok((mic, sys, pre, fmt));
+            }
+            LinuxBackend::Alsa => {
+                pre.system_prelude = vec!["-f".into(), "alsa".into()];
+                pre.mic_prelude = vec!["-f".into(), "alsa".into()];
+
+                let mic = mic_override.map(|s| s.to_string()).unwrap_or_else(|| "default".into());
+                let sys = system_override.map(|s| s.to_string()).unwrap_or_else(|| "default".into());
+
+                return Ok((mic, sys, pre, fmt));
+            }
+            LinuxBackend::Pulse => {}
+        }
+
         pre.system_prelude = vec!["-f".into(), "pulse".into()];
         pre.mic_prelude = vec!["-f".into(), "pulse".into()];
 
@@ -359,3 +547,39 @@ fn platform_inputs_with_overrides(
     Err(anyhow!("Unsupported platform"))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_template_numbers_the_stem_before_the_extension() {
+        let path = Path::new("/tmp/recordings/recording_2026-07-28_07-23-36.wav");
+        assert_eq!(
+            segment_template(path),
+            "/tmp/recordings/recording_2026-07-28_07-23-36_%03d.wav"
+        );
+    }
+
+    #[test]
+    fn segment_template_falls_back_for_unusual_paths() {
+        assert_eq!(segment_template(Path::new("")), "recording_%03d.wav");
+    }
+
+    #[test]
+    fn codec_config_defaults_match_container() {
+        let flac = CodecConfig::for_container(Container::Flac);
+        assert_eq!(flac.codec, "flac");
+        assert_eq!(flac.bitrate, None);
+
+        let opus = CodecConfig::for_container(Container::Opus);
+        assert_eq!(opus.codec, "libopus");
+        assert_eq!(opus.bitrate, Some(128));
+    }
+
+    #[test]
+    fn container_format_name_matches_ffmpeg_muxers() {
+        assert_eq!(container_format_name(Container::Wav), "wav");
+        assert_eq!(container_format_name(Container::Aac), "adts");
+    }
+}
+
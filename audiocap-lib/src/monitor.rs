@@ -0,0 +1,167 @@
+//! Live input-level monitoring, used for VU meters and voice-activated
+//! recording (see `vad`).
+
+use anyhow::{anyhow, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::SampleFormat;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// Frame size used for RMS windows; short enough to feel live, long enough
+/// to average out individual sample spikes.
+const FRAME_MS: u64 = 20;
+/// Smoothing factor for the level's exponential moving average. Lower is
+/// smoother (less VU-meter flicker) but slower to react.
+const EMA_ALPHA: f32 = 0.3;
+
+/// Handle to a running input-level monitor. `cpal::Stream` is `!Send`/`!Sync`
+/// (it holds platform callback state), so it can't live directly in shared
+/// app state; instead it's owned by a dedicated thread spawned in `start`,
+/// and this handle only exposes the `Send + Sync` level counter plus a way
+/// to ask that thread to stop. Dropping this (or explicitly stopping it)
+/// tears down the thread and its stream.
+pub struct InputMonitor {
+    level: Arc<AtomicU32>,
+    stop_tx: mpsc::Sender<()>,
+}
+
+impl InputMonitor {
+    /// Open `device_name` (or the host default input if `None`) on a
+    /// dedicated thread and start continuously tracking a smoothed RMS
+    /// level. `on_level` is called once per ~20 ms frame with the smoothed
+    /// level so callers can forward it onto an event stream (e.g. a Tauri
+    /// `emit_all`) without polling.
+    pub fn start(
+        device_name: Option<&str>,
+        on_level: impl FnMut(f32) + Send + 'static,
+    ) -> Result<Self> {
+        let device_name = device_name.map(str::to_string);
+        let level = Arc::new(AtomicU32::new(0f32.to_bits()));
+        let level_for_thread = Arc::clone(&level);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
+
+        thread::Builder::new()
+            .name("input-monitor".into())
+            .spawn(move || {
+                let stream =
+                    match open_stream(device_name.as_deref(), level_for_thread, on_level) {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        }
+                    };
+
+                let _ = ready_tx.send(Ok(()));
+                // Keep the stream alive until `stop_tx` is dropped/signalled.
+                let _ = stop_rx.recv();
+                drop(stream);
+            })
+            .context("failed to spawn input monitor thread")?;
+
+        ready_rx
+            .recv()
+            .context("input monitor thread exited before starting")??;
+
+        Ok(Self { level, stop_tx })
+    }
+
+    /// Current smoothed RMS level (0.0-ish to ~1.0 for full-scale audio).
+    /// Safe to poll from any thread, e.g. a frontend that prefers pulling
+    /// over subscribing to events.
+    pub fn get_input_level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for InputMonitor {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+    }
+}
+
+/// Build and start (`.play()`) the cpal input stream. Runs on the monitor's
+/// dedicated thread, since `cpal::Stream` can't be moved off of it.
+fn open_stream(
+    device_name: Option<&str>,
+    level: Arc<AtomicU32>,
+    mut on_level: impl FnMut(f32) + Send + 'static,
+) -> Result<cpal::Stream> {
+    let host = cpal::default_host();
+    let device = match device_name {
+        Some(name) => host
+            .input_devices()?
+            .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| anyhow!("input device '{}' not found", name))?,
+        None => host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("no default input device"))?,
+    };
+
+    let config = device
+        .default_input_config()
+        .context("failed to get default input config")?;
+    let sample_format = config.sample_format();
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+    let frame_len = ((sample_rate as u64 * FRAME_MS / 1000) as usize * channels).max(1);
+
+    let err_fn = |err| eprintln!("input monitor stream error: {err}");
+
+    let mut frame_buf: Vec<f32> = Vec::with_capacity(frame_len);
+    let mut push_frame = move |samples: &[f32]| {
+        for &sample in samples {
+            frame_buf.push(sample);
+            if frame_buf.len() >= frame_len {
+                let sum_sq: f32 = frame_buf.iter().map(|s| s * s).sum();
+                let rms = (sum_sq / frame_buf.len() as f32).sqrt();
+                let prev = f32::from_bits(level.load(Ordering::Relaxed));
+                let smoothed = EMA_ALPHA * rms + (1.0 - EMA_ALPHA) * prev;
+                level.store(smoothed.to_bits(), Ordering::Relaxed);
+                on_level(smoothed);
+                frame_buf.clear();
+            }
+        }
+    };
+
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _| push_frame(data),
+            err_fn,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _| {
+                let floats: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                push_frame(&floats);
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _| {
+                let floats: Vec<f32> = data
+                    .iter()
+                    .map(|&s| (s as f32 - u16::MAX as f32 / 2.0) / (u16::MAX as f32 / 2.0))
+                    .collect();
+                push_frame(&floats);
+            },
+            err_fn,
+            None,
+        )?,
+        other => bail_unsupported(other)?,
+    };
+
+    stream.play().context("failed to start input stream")?;
+
+    Ok(stream)
+}
+
+fn bail_unsupported(format: SampleFormat) -> Result<cpal::Stream> {
+    Err(anyhow!("unsupported input sample format: {:?}", format))
+}
@@ -0,0 +1,254 @@
+//! Live spectrum analysis of the input, for a spectrogram display and as a
+//! frequency-aware basis for silence/noise gating (distinguishing speech
+//! from steady background hum).
+//!
+//! Mono samples are pulled from the same kind of input stream as
+//! `monitor::InputMonitor` into a sliding N=2048 buffer with 50% hop, Hann
+//! windowed, and run through a real-to-complex FFT via `realfft`.
+
+use anyhow::{anyhow, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::SampleFormat;
+use realfft::RealFftPlanner;
+use std::f32::consts::PI;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// FFT window size: finer frequency resolution trades off against time
+/// resolution and compute.
+const FFT_SIZE: usize = 2048;
+/// 50% hop between consecutive windows.
+const HOP_SIZE: usize = FFT_SIZE / 2;
+/// Bins at or below this frequency (DC offset and sub-bass rumble) are
+/// excluded from the dominant-frequency search, since a DC/near-DC offset
+/// common on real capture devices would otherwise always win the argmax.
+const DOMINANT_HZ_FLOOR: f32 = 20.0;
+/// Minimum spacing between `on_frame` calls (~30 fps). Frames analyzed in
+/// between still update what `spectrum_frame` returns.
+const EMIT_INTERVAL: Duration = Duration::from_millis(33);
+
+/// One analyzed frame: a log-scaled magnitude per frequency bin (length
+/// `FFT_SIZE / 2 + 1`), plus the single dominant frequency in Hz.
+#[derive(Clone, Debug)]
+pub struct SpectrumFrame {
+    pub magnitudes_db: Vec<f32>,
+    pub dominant_hz: f32,
+}
+
+/// Handle to a running spectrum analyzer. `cpal::Stream` is `!Send`/`!Sync`
+/// (it holds platform callback state), so it can't live directly in shared
+/// app state; instead it's owned by a dedicated thread spawned in `start`,
+/// and this handle only exposes the `Send + Sync` latest-frame buffer plus
+/// a way to ask that thread to stop. Dropping this (or explicitly stopping
+/// it) tears down the thread and its stream.
+pub struct SpectrumAnalyzer {
+    latest: Arc<Mutex<SpectrumFrame>>,
+    stop_tx: mpsc::Sender<()>,
+}
+
+impl SpectrumAnalyzer {
+    /// Open `device_name` (or the host default input if `None`) on a
+    /// dedicated thread and start computing spectrum frames at one per
+    /// `HOP_SIZE` samples. `on_frame` is called with the newest frame at
+    /// most once per `EMIT_INTERVAL` (~30 fps) so callers can forward it
+    /// onto an event stream.
+    pub fn start(
+        device_name: Option<&str>,
+        on_frame: impl FnMut(&SpectrumFrame) + Send + 'static,
+    ) -> Result<Self> {
+        let device_name = device_name.map(str::to_string);
+        let latest = Arc::new(Mutex::new(SpectrumFrame {
+            magnitudes_db: vec![0.0; FFT_SIZE / 2 + 1],
+            dominant_hz: 0.0,
+        }));
+        let latest_for_thread = Arc::clone(&latest);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
+
+        thread::Builder::new()
+            .name("spectrum-analyzer".into())
+            .spawn(move || {
+                let stream =
+                    match open_stream(device_name.as_deref(), latest_for_thread, on_frame) {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        }
+                    };
+
+                let _ = ready_tx.send(Ok(()));
+                // Keep the stream alive until `stop_tx` is dropped/signalled.
+                let _ = stop_rx.recv();
+                drop(stream);
+            })
+            .context("failed to spawn spectrum analyzer thread")?;
+
+        ready_rx
+            .recv()
+            .context("spectrum analyzer thread exited before starting")??;
+
+        Ok(Self { latest, stop_tx })
+    }
+
+    /// Log-scaled power vector from the most recent analyzed frame, for
+    /// non-UI callers (e.g. a noise gate) that don't want to subscribe to
+    /// the `on_frame` callback.
+    pub fn spectrum_frame(&self) -> Vec<f32> {
+        self.latest.lock().unwrap().magnitudes_db.clone()
+    }
+
+    /// Dominant frequency (Hz) from the most recent analyzed frame.
+    pub fn dominant_hz(&self) -> f32 {
+        self.latest.lock().unwrap().dominant_hz
+    }
+}
+
+impl Drop for SpectrumAnalyzer {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+    }
+}
+
+/// Build and start (`.play()`) the cpal input stream. Runs on the
+/// analyzer's dedicated thread, since `cpal::Stream` can't be moved off of
+/// it.
+fn open_stream(
+    device_name: Option<&str>,
+    latest: Arc<Mutex<SpectrumFrame>>,
+    mut on_frame: impl FnMut(&SpectrumFrame) + Send + 'static,
+) -> Result<cpal::Stream> {
+    let host = cpal::default_host();
+    let device = match device_name {
+        Some(name) => host
+            .input_devices()?
+            .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| anyhow!("input device '{}' not found", name))?,
+        None => host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("no default input device"))?,
+    };
+
+    let config = device
+        .default_input_config()
+        .context("failed to get default input config")?;
+    let sample_format = config.sample_format();
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(FFT_SIZE);
+    let mut scratch = fft.make_scratch_vec();
+    let mut spectrum = fft.make_output_vec();
+
+    // w[n] = 0.5*(1 - cos(2*pi*n/(N-1)))
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|n| 0.5 * (1.0 - (2.0 * PI * n as f32 / (FFT_SIZE as f32 - 1.0)).cos()))
+        .collect();
+
+    // Bins at or below `DOMINANT_HZ_FLOOR` are excluded from the dominant-bin
+    // search (see the constant's doc comment).
+    let dominant_min_bin = ((DOMINANT_HZ_FLOOR * FFT_SIZE as f32 / sample_rate as f32).floor()
+        as usize)
+        + 1;
+
+    let mut last_emit: Option<Instant> = None;
+    let mut ring: Vec<f32> = Vec::with_capacity(FFT_SIZE * 2);
+    let mut process_mono = move |mono: &[f32]| {
+        ring.extend_from_slice(mono);
+        while ring.len() >= FFT_SIZE {
+            let mut windowed: Vec<f32> = ring[..FFT_SIZE]
+                .iter()
+                .zip(window.iter())
+                .map(|(s, w)| s * w)
+                .collect();
+
+            if fft
+                .process_with_scratch(&mut windowed, &mut spectrum, &mut scratch)
+                .is_ok()
+            {
+                let magnitudes: Vec<f32> = spectrum
+                    .iter()
+                    .map(|c| (c.re * c.re + c.im * c.im).sqrt())
+                    .collect();
+
+                let dominant_bin = magnitudes
+                    .iter()
+                    .enumerate()
+                    .skip(dominant_min_bin)
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(i, _)| i)
+                    .unwrap_or(dominant_min_bin);
+                let dominant_hz = dominant_bin as f32 * sample_rate as f32 / FFT_SIZE as f32;
+
+                let magnitudes_db: Vec<f32> = magnitudes
+                    .iter()
+                    .map(|m| 20.0 * m.max(1e-9).log10())
+                    .collect();
+
+                let frame = SpectrumFrame {
+                    magnitudes_db,
+                    dominant_hz,
+                };
+                *latest.lock().unwrap() = frame.clone();
+                if last_emit.map_or(true, |t| t.elapsed() >= EMIT_INTERVAL) {
+                    last_emit = Some(Instant::now());
+                    on_frame(&frame);
+                }
+            }
+
+            ring.drain(..HOP_SIZE);
+        }
+    };
+
+    let err_fn = |err| eprintln!("spectrum stream error: {err}");
+
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _| process_mono(&downmix(data, channels)),
+            err_fn,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _| {
+                let floats: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                process_mono(&downmix(&floats, channels));
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _| {
+                let floats: Vec<f32> = data
+                    .iter()
+                    .map(|&s| (s as f32 - u16::MAX as f32 / 2.0) / (u16::MAX as f32 / 2.0))
+                    .collect();
+                process_mono(&downmix(&floats, channels));
+            },
+            err_fn,
+            None,
+        )?,
+        other => return Err(anyhow!("unsupported input sample format: {:?}", other)),
+    };
+
+    stream
+        .play()
+        .context("failed to start spectrum input stream")?;
+
+    Ok(stream)
+}
+
+/// Average interleaved multi-channel samples down to mono.
+fn downmix(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
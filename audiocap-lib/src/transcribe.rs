@@ -0,0 +1,414 @@
+//! Optional local speech-to-text transcription for finished recordings, via
+//! a local Whisper model (through `whisper-rs`).
+//!
+//! Long recordings are chunked into ~30s windows (with a small overlap so
+//! words aren't clipped at chunk boundaries) and run through the model
+//! sequentially, to keep memory bounded regardless of recording length.
+
+use crate::start_piped;
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+/// Window size used when chunking long recordings for sequential inference.
+const CHUNK_SECONDS: f64 = 30.0;
+/// Overlap between consecutive chunks, so a word spoken right at a chunk
+/// boundary isn't clipped out of both chunks.
+const CHUNK_OVERLAP_SECONDS: f64 = 1.0;
+/// Whisper models are trained on 16 kHz mono audio.
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Whisper model size to load. Larger models are more accurate but slower
+/// and use more memory.
+#[derive(Copy, Clone, Debug)]
+pub enum ModelSize {
+    Tiny,
+    Base,
+    Small,
+    Medium,
+    Large,
+}
+
+impl ModelSize {
+    fn ggml_filename(self) -> &'static str {
+        match self {
+            ModelSize::Tiny => "ggml-tiny.bin",
+            ModelSize::Base => "ggml-base.bin",
+            ModelSize::Small => "ggml-small.bin",
+            ModelSize::Medium => "ggml-medium.bin",
+            ModelSize::Large => "ggml-large.bin",
+        }
+    }
+}
+
+/// Options controlling a transcription run.
+#[derive(Clone, Debug)]
+pub struct TranscribeOptions {
+    pub model_size: ModelSize,
+    /// Force a language (e.g. "en"), or let Whisper auto-detect.
+    pub language: Option<String>,
+    pub word_timestamps: bool,
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            model_size: ModelSize::Base,
+            language: None,
+            word_timestamps: false,
+        }
+    }
+}
+
+/// A single word/token with its position in the source audio. Only
+/// populated when `TranscribeOptions::word_timestamps` is set.
+#[derive(Clone, Debug)]
+pub struct WordTimestamp {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+}
+
+/// A single transcribed utterance with its position in the source audio.
+#[derive(Clone, Debug)]
+pub struct TranscriptSegment {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+    /// Per-word timings within this segment; empty unless
+    /// `TranscribeOptions::word_timestamps` was set.
+    pub words: Vec<WordTimestamp>,
+}
+
+/// Full transcript for one recording.
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl Transcript {
+    /// Render as SubRip (`.srt`) subtitle text.
+    pub fn to_srt(&self) -> String {
+        let mut out = String::new();
+        for (i, seg) in self.segments.iter().enumerate() {
+            out.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_srt_timestamp(seg.start),
+                format_srt_timestamp(seg.end),
+                seg.text.trim()
+            ));
+        }
+        out
+    }
+
+    /// Render as a small JSON document (kept dependency-free; hand-built
+    /// rather than pulling in serde_json just for this shape). Segments
+    /// carry a `"words"` array when `TranscribeOptions::word_timestamps` was
+    /// set, otherwise it's empty.
+    pub fn to_json(&self) -> String {
+        let segments: Vec<String> = self
+            .segments
+            .iter()
+            .map(|seg| {
+                let words: Vec<String> = seg
+                    .words
+                    .iter()
+                    .map(|w| {
+                        format!(
+                            "{{\"start\":{:.3},\"end\":{:.3},\"text\":{:?}}}",
+                            w.start.as_secs_f64(),
+                            w.end.as_secs_f64(),
+                            w.text.trim()
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"start\":{:.3},\"end\":{:.3},\"text\":{:?},\"words\":[{}]}}",
+                    seg.start.as_secs_f64(),
+                    seg.end.as_secs_f64(),
+                    seg.text.trim(),
+                    words.join(",")
+                )
+            })
+            .collect();
+        format!("{{\"segments\":[{}]}}", segments.join(","))
+    }
+}
+
+/// Number of chunks the `transcribe` loop below actually runs, mirroring its
+/// stride/break logic exactly: it stops as soon as a chunk reaches the end
+/// of `len`, so a naive `len.div_ceil(stride)` overcounts by one for some
+/// lengths, e.g. whenever `len` is longer than `stride` but still fits in a
+/// single `chunk_len` window.
+fn total_chunks_for(len: usize, chunk_len: usize, stride: usize) -> usize {
+    if len <= chunk_len {
+        1
+    } else {
+        1 + (len - chunk_len).div_ceil(stride)
+    }
+}
+
+fn format_srt_timestamp(d: Duration) -> String {
+    let total_ms = d.as_millis();
+    let ms = total_ms % 1000;
+    let total_secs = total_ms / 1000;
+    let secs = total_secs % 60;
+    let total_mins = total_secs / 60;
+    let mins = total_mins % 60;
+    let hours = total_mins / 60;
+    format!("{:02}:{:02}:{:02},{:03}", hours, mins, secs, ms)
+}
+
+/// Transcribe `path` (any format ffmpeg can decode) with a local Whisper
+/// model, reporting progress as `(chunks_done, chunks_total)` after each
+/// chunk so a caller can drive a progress bar.
+pub fn transcribe(
+    path: &Path,
+    opts: &TranscribeOptions,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<Transcript> {
+    let samples = decode_to_mono16k(path)?;
+
+    let model_path = model_path(opts.model_size)?;
+    let ctx = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .context("failed to load whisper model")?;
+
+    let chunk_len = (CHUNK_SECONDS * WHISPER_SAMPLE_RATE as f64) as usize;
+    let overlap_len = (CHUNK_OVERLAP_SECONDS * WHISPER_SAMPLE_RATE as f64) as usize;
+    let stride = chunk_len.saturating_sub(overlap_len).max(1);
+    let total_chunks = total_chunks_for(samples.len(), chunk_len, stride);
+    // Every chunk after the first repeats this chunk's leading
+    // `CHUNK_OVERLAP_SECONDS` of audio, which the previous chunk already
+    // transcribed; skip anything whose start falls in that shared region so
+    // it isn't emitted twice.
+    let overlap_duration = Duration::from_secs_f64(CHUNK_OVERLAP_SECONDS);
+
+    let mut segments = Vec::new();
+    let mut start = 0usize;
+    let mut chunks_done = 0usize;
+
+    while start < samples.len() {
+        let end = (start + chunk_len).min(samples.len());
+        let chunk = &samples[start..end];
+        let chunk_offset = Duration::from_secs_f64(start as f64 / WHISPER_SAMPLE_RATE as f64);
+        let is_first_chunk = chunks_done == 0;
+
+        let mut state = ctx.create_state().context("failed to create whisper state")?;
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_token_timestamps(opts.word_timestamps);
+        if let Some(lang) = opts.language.as_deref() {
+            params.set_language(Some(lang));
+        }
+        state.full(params, chunk).context("whisper inference failed")?;
+
+        let n = state
+            .full_n_segments()
+            .context("failed to read whisper segment count")?;
+        for i in 0..n {
+            let text = state
+                .full_get_segment_text(i)
+                .context("failed to read whisper segment text")?;
+            // Whisper reports timestamps in centiseconds, relative to this chunk.
+            let t0 = state.full_get_segment_t0(i).unwrap_or(0);
+            let t1 = state.full_get_segment_t1(i).unwrap_or(t0);
+            let seg_start = Duration::from_millis(t0.max(0) as u64 * 10);
+            let seg_end = Duration::from_millis(t1.max(0) as u64 * 10);
+
+            if is_in_prior_overlap(is_first_chunk, seg_start, overlap_duration) {
+                continue;
+            }
+
+            let words = if opts.word_timestamps {
+                collect_words(&state, i, chunk_offset, is_first_chunk, overlap_duration)
+            } else {
+                Vec::new()
+            };
+
+            segments.push(TranscriptSegment {
+                start: chunk_offset + seg_start,
+                end: chunk_offset + seg_end,
+                text,
+                words,
+            });
+        }
+
+        chunks_done += 1;
+        on_progress(chunks_done, total_chunks);
+
+        if end == samples.len() {
+            break;
+        }
+        start += stride;
+    }
+
+    Ok(Transcript { segments })
+}
+
+/// Per-word timings for whisper segment `i`, filtered the same way as the
+/// segments themselves so a word from the leading overlap of a non-first
+/// chunk (already covered by the previous chunk) isn't duplicated.
+fn collect_words(
+    state: &WhisperState,
+    segment_index: i32,
+    chunk_offset: Duration,
+    is_first_chunk: bool,
+    overlap_duration: Duration,
+) -> Vec<WordTimestamp> {
+    let token_count = state.full_n_tokens(segment_index).unwrap_or(0);
+    let mut words = Vec::new();
+
+    for j in 0..token_count {
+        let Ok(text) = state.full_get_token_text(segment_index, j) else {
+            continue;
+        };
+        let Ok(data) = state.full_get_token_data(segment_index, j) else {
+            continue;
+        };
+        let word_start = Duration::from_millis(data.t0.max(0) as u64 * 10);
+        let word_end = Duration::from_millis(data.t1.max(0) as u64 * 10);
+
+        if is_in_prior_overlap(is_first_chunk, word_start, overlap_duration) {
+            continue;
+        }
+
+        words.push(WordTimestamp {
+            start: chunk_offset + word_start,
+            end: chunk_offset + word_end,
+            text,
+        });
+    }
+
+    words
+}
+
+/// True if a segment/word starting at `start_in_chunk` (relative to the
+/// current chunk) falls inside that chunk's leading overlap with the
+/// previous chunk, meaning the previous chunk's pass already emitted it.
+/// Always `false` for the first chunk, since there is no previous chunk to
+/// have covered it.
+fn is_in_prior_overlap(is_first_chunk: bool, start_in_chunk: Duration, overlap_duration: Duration) -> bool {
+    !is_first_chunk && start_in_chunk < overlap_duration
+}
+
+/// Write `.srt` and `.json` sidecar files next to `media_path`, e.g.
+/// `recording_2026-07-28.wav` -> `recording_2026-07-28.srt` / `.json`.
+pub fn write_sidecars(media_path: &Path, transcript: &Transcript) -> Result<()> {
+    let srt_path = media_path.with_extension("srt");
+    let json_path = media_path.with_extension("json");
+    std::fs::write(&srt_path, transcript.to_srt()).context("failed to write .srt sidecar")?;
+    std::fs::write(&json_path, transcript.to_json()).context("failed to write .json sidecar")?;
+    Ok(())
+}
+
+/// Decode `path` to 16 kHz mono `f32` PCM via ffmpeg, the same binary the
+/// rest of this crate already requires.
+fn decode_to_mono16k(path: &Path) -> Result<Vec<f32>> {
+    let args = vec![
+        "-hide_banner".into(),
+        "-y".into(),
+        "-i".into(),
+        path.to_string_lossy().to_string(),
+        "-ac".into(),
+        "1".into(),
+        "-ar".into(),
+        WHISPER_SAMPLE_RATE.to_string(),
+        "-f".into(),
+        "f32le".into(),
+        "pipe:1".into(),
+    ];
+
+    let mut child = start_piped(&args)?;
+    let mut stdout = child.stdout.take().context("ffmpeg stdout was not piped")?;
+    let mut buf = Vec::new();
+    stdout
+        .read_to_end(&mut buf)
+        .context("failed to read decoded audio from ffmpeg")?;
+    let status = child.wait().context("ffmpeg decode failed to run")?;
+    if !status.success() {
+        anyhow::bail!("ffmpeg exited with status: {}", status);
+    }
+
+    Ok(buf
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+fn model_path(size: ModelSize) -> Result<PathBuf> {
+    let path = PathBuf::from("models").join(size.ggml_filename());
+    if !path.exists() {
+        anyhow::bail!(
+            "whisper model not found at {}; download a ggml model into ./models",
+            path.display()
+        );
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_chunks_for_single_short_recording() {
+        // A recording no longer than one chunk window runs exactly one chunk;
+        // a plain `len.div_ceil(stride)` would say two for the 30_000 case.
+        assert_eq!(total_chunks_for(1_000, 30_000, 29_000), 1);
+        assert_eq!(total_chunks_for(30_000, 30_000, 29_000), 1);
+    }
+
+    #[test]
+    fn total_chunks_for_matches_loop_iterations() {
+        let chunk_len = 30_000;
+        let stride = 29_000;
+        for len in [30_001, 59_000, 59_001, 120_000] {
+            let expected = {
+                let mut start = 0usize;
+                let mut iterations = 0usize;
+                loop {
+                    let end = (start + chunk_len).min(len);
+                    iterations += 1;
+                    if end == len {
+                        break;
+                    }
+                    start += stride;
+                }
+                iterations
+            };
+            assert_eq!(total_chunks_for(len, chunk_len, stride), expected, "len={len}");
+        }
+    }
+
+    #[test]
+    fn first_chunk_segments_are_never_treated_as_overlap() {
+        let overlap = Duration::from_secs(1);
+        assert!(!is_in_prior_overlap(true, Duration::ZERO, overlap));
+        assert!(!is_in_prior_overlap(true, Duration::from_millis(500), overlap));
+    }
+
+    #[test]
+    fn later_chunk_segments_in_the_overlap_window_are_skipped() {
+        let overlap = Duration::from_secs(1);
+        assert!(is_in_prior_overlap(false, Duration::ZERO, overlap));
+        assert!(is_in_prior_overlap(false, Duration::from_millis(999), overlap));
+        assert!(!is_in_prior_overlap(false, Duration::from_secs(1), overlap));
+        assert!(!is_in_prior_overlap(false, Duration::from_millis(1500), overlap));
+    }
+
+    #[test]
+    fn srt_timestamp_formatting() {
+        assert_eq!(format_srt_timestamp(Duration::ZERO), "00:00:00,000");
+        assert_eq!(
+            format_srt_timestamp(Duration::from_millis(3_661_042)),
+            "01:01:01,042"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Voice-activated recording on top of `monitor::InputMonitor`.
+//!
+//! The recorder stays armed (the monitor stream keeps running) but ffmpeg is
+//! only spawned while the smoothed input level is above
+//! `RecorderConfig::vad_threshold`, and is stopped again after
+//! `RecorderConfig::silence_timeout` of sub-threshold audio. Hysteresis
+//! (closing at 0.6x the open threshold) keeps the gate from chattering right
+//! at the boundary.
+
+use crate::monitor::InputMonitor;
+use crate::{build_ffmpeg_args, next_filename, start_background, stop_pid, OutputSink, RecorderConfig};
+use anyhow::{anyhow, Result};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Fraction of `vad_threshold` used as the close threshold, so the gate
+/// opens and closes at different levels instead of chattering around one.
+const HYSTERESIS_CLOSE_RATIO: f32 = 0.6;
+
+/// Run voice-activated recording against `cfg` until `stop_rx` receives a
+/// value (or is dropped). Keeps `device_name`'s input armed via
+/// `InputMonitor` the whole time, starting/stopping ffmpeg as the level
+/// crosses the open/close thresholds. Blocks the calling thread, so run it
+/// on a dedicated thread or `spawn_blocking`.
+pub fn run_voice_activated(
+    cfg: &RecorderConfig,
+    device_name: Option<&str>,
+    stop_rx: mpsc::Receiver<()>,
+) -> Result<()> {
+    let open_threshold = cfg
+        .vad_threshold
+        .ok_or_else(|| anyhow!("vad_threshold must be set to run voice-activated mode"))?;
+    let close_threshold = open_threshold * HYSTERESIS_CLOSE_RATIO;
+
+    let (level_tx, level_rx) = mpsc::channel::<f32>();
+    let _monitor = InputMonitor::start(device_name, move |level| {
+        let _ = level_tx.send(level);
+    })?;
+
+    let mut current_pid: Option<u32> = None;
+    let mut last_above_close = Instant::now();
+
+    loop {
+        if !matches!(stop_rx.try_recv(), Err(mpsc::TryRecvError::Empty)) {
+            break;
+        }
+
+        match level_rx.recv_timeout(Duration::from_millis(50)) {
+            Ok(level) => {
+                if level >= close_threshold {
+                    last_above_close = Instant::now();
+                }
+
+                match current_pid {
+                    None if level >= open_threshold => {
+                        let outfile = next_filename(&cfg.out_dir, cfg.codec.container);
+                        let args = build_ffmpeg_args(cfg, &OutputSink::File(outfile), None, None, None)?;
+                        current_pid = Some(start_background(&args)?);
+                    }
+                    Some(pid) if last_above_close.elapsed() >= cfg.silence_timeout => {
+                        stop_pid(pid)?;
+                        current_pid = None;
+                    }
+                    _ => {}
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    if let Some(pid) = current_pid {
+        stop_pid(pid)?;
+    }
+
+    Ok(())
+}
@@ -6,8 +6,10 @@ use std::path::PathBuf;
 use anyhow::Context;
 
 use audiocap_lib::{
-    build_ffmpeg_args, check_ffmpeg, is_pid_alive, list_devices, next_filename, start_background,
-    start_foreground, stop_pid, Container, RecorderConfig,
+    build_ffmpeg_args, check_ffmpeg, ffmpeg_input_for_device, is_pid_alive, list_devices_structured,
+    next_filename, recommended_backend, start_background, start_foreground, stop_pid, transcribe,
+    Container, InputMonitor, ModelSize, OutputSink, RecorderConfig, SpectrumAnalyzer,
+    TranscribeOptions,
 };
 
 /// Small state struct so we can keep track of last background pid if desired.
@@ -15,6 +17,10 @@ use audiocap_lib::{
 struct AppState {
     // Optionally you can keep an in-memory handle; we only store last pid for convenience.
     last_pid: std::sync::Mutex<Option<u32>>,
+    // Live input monitor for the VU meter / voice-activated mode, if started.
+    monitor: std::sync::Mutex<Option<InputMonitor>>,
+    // Live spectrum analyzer for the spectrogram display, if started.
+    spectrum: std::sync::Mutex<Option<SpectrumAnalyzer>>,
 }
 
 
@@ -27,8 +33,16 @@ async fn start_recording(
     background: bool,
     // optional duration "00:10:00" or seconds "600"
     duration: Option<String>,
+    // raw ffmpeg input strings (advanced/manual use)
     mic: Option<String>,
     system: Option<String>,
+    // `DeviceInfo.id`s from `list_audio_devices`; when given, these win over
+    // `mic`/`system` and are mapped to a valid ffmpeg input via
+    // `ffmpeg_input_for_device` instead of being guessed
+    mic_device_id: Option<String>,
+    system_device_id: Option<String>,
+    // stream to a live endpoint (rtmp://, icecast://, ...) instead of a file
+    stream_url: Option<String>,
 ) -> Result<serde_json::Value, String> {
     // ---- Simplified: require system ffmpeg on PATH ----
     // audiocap-lib::check_ffmpeg() returns Err if ffmpeg is not found on PATH.
@@ -53,26 +67,44 @@ async fn start_recording(
         .app_data_dir()
         .ok_or("failed to resolve app data dir".to_string())?;
     cfg.out_dir = data_dir.join("recordings");
-    cfg.format = Container::Wav; // or accept a parameter from frontend
+    cfg.codec = audiocap_lib::CodecConfig::for_container(Container::Wav); // or accept a parameter from frontend
 
     // ensure it exists
     std::fs::create_dir_all(&cfg.out_dir).map_err(|e| e.to_string())?;
 
-    // Choose output file
-    let outfile: PathBuf = match output {
-        Some(s) => PathBuf::from(s),
-        None => audiocap_lib::next_filename(&cfg.out_dir, cfg.format),
+    // Resolve a structured device id to a real ffmpeg input string (and the
+    // matching backend), instead of forwarding a raw device name that's
+    // missing the platform-specific prefix ffmpeg expects.
+    let mic_override = resolve_device_override(mic_device_id.as_deref(), mic, &mut cfg)?;
+    let system_override = resolve_device_override(system_device_id.as_deref(), system, &mut cfg)?;
+
+    // Choose output sink: a live URL if given, otherwise a file.
+    let sink = match stream_url {
+        Some(url) => OutputSink::Url(url),
+        None => {
+            let outfile: PathBuf = match output {
+                Some(s) => PathBuf::from(s),
+                None => audiocap_lib::next_filename(&cfg.out_dir, cfg.codec.container),
+            };
+            OutputSink::File(outfile)
+        }
     };
 
     let args = build_ffmpeg_args(
         &cfg,
-        &outfile,
+        &sink,
         duration.as_deref(),
-        mic.as_deref(),
-        system.as_deref(),
+        mic_override.as_deref(),
+        system_override.as_deref(),
     )
     .map_err(|e| e.to_string())?;
 
+    let outfile = match &sink {
+        OutputSink::File(path) => path.clone(),
+        OutputSink::Url(url) => PathBuf::from(url),
+        OutputSink::Stdout => PathBuf::from("pipe:1"),
+    };
+
     if background {
         // Start background: spawn detached and write pidfile
         let pid = start_background(&args).map_err(|e| e.to_string())?;
@@ -162,14 +194,143 @@ async fn status(app_handle: tauri::AppHandle) -> Result<serde_json::Value, Strin
 }
 
 #[tauri::command]
-async fn list_audio_devices() -> Result<String, String> {
-    // run list_devices in blocking thread (it prints to stdout/stderr)
-    tauri::async_runtime::spawn_blocking(move || {
-        list_devices(false, false).map_err(|e| format!("list_devices failed: {}", e))
+async fn list_audio_devices() -> Result<serde_json::Value, String> {
+    // run enumeration in a blocking thread since cpal's host/device calls aren't async
+    let devices = tauri::async_runtime::spawn_blocking(list_devices_structured)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("list_devices_structured failed: {}", e))?;
+
+    serde_json::to_value(devices).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn start_level_monitor(app_handle: tauri::AppHandle, device: Option<String>) -> Result<(), String> {
+    let emitter = app_handle.clone();
+    let monitor = InputMonitor::start(device.as_deref(), move |level| {
+        let _ = emitter.emit_all("input-level", level);
+    })
+    .map_err(|e| e.to_string())?;
+
+    let state = app_handle.state::<AppState>();
+    *state.monitor.lock().unwrap() = Some(monitor);
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_level_monitor(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    *state.monitor.lock().unwrap() = None;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_input_level(app_handle: tauri::AppHandle) -> Result<f32, String> {
+    let state = app_handle.state::<AppState>();
+    let guard = state.monitor.lock().unwrap();
+    Ok(guard.as_ref().map(|m| m.get_input_level()).unwrap_or(0.0))
+}
+
+#[tauri::command]
+async fn transcribe_recording(
+    app_handle: tauri::AppHandle,
+    path: String,
+    model_size: Option<String>,
+    language: Option<String>,
+    word_timestamps: bool,
+) -> Result<serde_json::Value, String> {
+    let path = PathBuf::from(path);
+    let opts = TranscribeOptions {
+        model_size: parse_model_size(model_size.as_deref()),
+        language,
+        word_timestamps,
+    };
+
+    let emitter = app_handle.clone();
+    let path_for_blocking = path.clone();
+    let transcript = tauri::async_runtime::spawn_blocking(move || {
+        transcribe(&path_for_blocking, &opts, move |done, total| {
+            let _ = emitter.emit_all(
+                "transcription-progress",
+                serde_json::json!({ "done": done, "total": total }),
+            );
+        })
     })
     .await
     .map_err(|e| e.to_string())?
-    .map(|_| "done".into())
+    .map_err(|e| e.to_string())?;
+
+    audiocap_lib::transcribe::write_sidecars(&path, &transcript).map_err(|e| e.to_string())?;
+
+    Ok(serde_json::json!({
+        "srt": path.with_extension("srt").to_string_lossy(),
+        "json": path.with_extension("json").to_string_lossy(),
+        "segments": transcript.segments.len(),
+    }))
+}
+
+#[tauri::command]
+async fn start_spectrum(app_handle: tauri::AppHandle, device: Option<String>) -> Result<(), String> {
+    let emitter = app_handle.clone();
+    let analyzer = SpectrumAnalyzer::start(device.as_deref(), move |frame| {
+        let _ = emitter.emit_all(
+            "spectrum-frame",
+            serde_json::json!({
+                "magnitudes_db": frame.magnitudes_db,
+                "dominant_hz": frame.dominant_hz,
+            }),
+        );
+    })
+    .map_err(|e| e.to_string())?;
+
+    let state = app_handle.state::<AppState>();
+    *state.spectrum.lock().unwrap() = Some(analyzer);
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_spectrum(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    *state.spectrum.lock().unwrap() = None;
+    Ok(())
+}
+
+fn parse_model_size(s: Option<&str>) -> ModelSize {
+    match s {
+        Some("tiny") => ModelSize::Tiny,
+        Some("small") => ModelSize::Small,
+        Some("medium") => ModelSize::Medium,
+        Some("large") => ModelSize::Large,
+        _ => ModelSize::Base,
+    }
+}
+
+/// Resolve a mic/system override for `build_ffmpeg_args`. If `device_id`
+/// matches a device from `list_devices_structured`, map it to a real ffmpeg
+/// input via `ffmpeg_input_for_device` and apply its recommended backend to
+/// `cfg` (so the `-f` prelude ffmpeg uses matches what the input expects).
+/// With no `device_id`, returns the raw `raw_override` string as before; a
+/// `device_id` that matches no enumerated device is an error.
+fn resolve_device_override(
+    device_id: Option<&str>,
+    raw_override: Option<String>,
+    cfg: &mut RecorderConfig,
+) -> Result<Option<String>, String> {
+    let Some(device_id) = device_id else {
+        return Ok(raw_override);
+    };
+
+    let devices = list_devices_structured().map_err(|e| e.to_string())?;
+    let device = devices
+        .into_iter()
+        .find(|d| d.id == device_id)
+        .ok_or_else(|| format!("unknown device id: {}", device_id))?;
+
+    let (wasapi, linux_backend) = recommended_backend(&device);
+    cfg.wasapi = wasapi;
+    cfg.linux_backend = linux_backend;
+
+    ffmpeg_input_for_device(&device).map(Some).map_err(|e| e.to_string())
 }
 
 fn add_resource_dir_to_path(app_handle: &tauri::AppHandle) -> anyhow::Result<()> {
@@ -204,12 +365,20 @@ fn main() {
     tauri::Builder::default()
         .manage(AppState {
             last_pid: std::sync::Mutex::new(None),
+            monitor: std::sync::Mutex::new(None),
+            spectrum: std::sync::Mutex::new(None),
         })
         .invoke_handler(tauri::generate_handler![
             start_recording,
             stop_recording,
             status,
-            list_audio_devices
+            list_audio_devices,
+            start_level_monitor,
+            stop_level_monitor,
+            get_input_level,
+            transcribe_recording,
+            start_spectrum,
+            stop_spectrum
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");